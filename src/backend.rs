@@ -0,0 +1,355 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::ResolvedBookmark;
+
+/// An output target that bookmarks can be rendered into.
+///
+/// Each variant corresponds to one [`Formatter`] implementation; adding a new
+/// target means adding a variant here and a formatter below, not touching
+/// `main`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Lf,
+    Zsh,
+    CdAlias,
+    Fish,
+    Bash,
+    Nnn,
+    Ranger,
+    Json,
+}
+
+impl Backend {
+    /// Returns the [`Formatter`] implementation for this backend.
+    pub fn formatter(self) -> Box<dyn Formatter> {
+        match self {
+            Backend::Lf => Box::new(LfFormatter),
+            Backend::Zsh => Box::new(ZshFormatter),
+            Backend::CdAlias => Box::new(CdAliasFormatter),
+            Backend::Fish => Box::new(FishFormatter),
+            Backend::Bash => Box::new(BashFormatter),
+            Backend::Nnn => Box::new(NnnFormatter),
+            Backend::Ranger => Box::new(RangerFormatter),
+            Backend::Json => Box::new(JsonFormatter),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lf" => Ok(Backend::Lf),
+            "zsh" => Ok(Backend::Zsh),
+            "cd-alias" => Ok(Backend::CdAlias),
+            "fish" => Ok(Backend::Fish),
+            "bash" => Ok(Backend::Bash),
+            "nnn" => Ok(Backend::Nnn),
+            "ranger" => Ok(Backend::Ranger),
+            "json" => Ok(Backend::Json),
+            other => Err(format!(
+                "unknown backend '{other}', expected one of: lf, zsh, cd-alias, fish, bash, nnn, ranger, json"
+            )),
+        }
+    }
+}
+
+/// Renders bookmarks into one output target's file format.
+///
+/// Implementors only need [`Formatter::line`]; [`Formatter::header`],
+/// [`Formatter::separator`], [`Formatter::footer`] and the default
+/// [`Formatter::render`] cover the common "one line per alias" case,
+/// emitting a line for every alias of every bookmark. Formats that need the
+/// whole bookmark list at once to build a line (e.g. a `CDPATH` built from
+/// every path) override `render` directly instead.
+pub trait Formatter {
+    /// A line emitted once before any bookmark lines, if the format needs one.
+    fn header(&self) -> Option<String> {
+        None
+    }
+
+    /// Renders a single alias/path pair as one line of output.
+    fn line(&self, alias: &str, path: &Path) -> String;
+
+    /// Printed between two consecutive alias lines. Defaults to a newline;
+    /// formats whose lines are list elements (e.g. JSON array entries)
+    /// override this to add a trailing comma.
+    fn separator(&self) -> &str {
+        "\n"
+    }
+
+    /// A line emitted once after all bookmark lines, if the format needs one.
+    fn footer(&self) -> Option<String> {
+        None
+    }
+
+    /// Renders the full file contents, one line per alias of every bookmark.
+    fn render(&self, bookmarks: &[ResolvedBookmark<'_>]) -> String {
+        let lines: Vec<String> = bookmarks
+            .iter()
+            .flat_map(|b| b.aliases.iter().map(|alias| self.line(alias, &b.path)))
+            .collect();
+
+        let mut out = String::new();
+        if let Some(header) = self.header() {
+            out.push_str(&header);
+            out.push('\n');
+        }
+        out.push_str(&lines.join(self.separator()));
+        if !lines.is_empty() {
+            out.push('\n');
+        }
+        if let Some(footer) = self.footer() {
+            out.push_str(&footer);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+struct LfFormatter;
+
+impl Formatter for LfFormatter {
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!("map g{} cd {}", alias, path.display())
+    }
+}
+
+struct ZshFormatter;
+
+impl Formatter for ZshFormatter {
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!("hash -d {}={}", alias, path.display())
+    }
+}
+
+struct CdAliasFormatter;
+
+impl Formatter for CdAliasFormatter {
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!(r#"alias cd{}="{}""#, alias, path.display())
+    }
+}
+
+struct FishFormatter;
+
+impl Formatter for FishFormatter {
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!(
+            "abbr -a {0} 'cd {1}'\nfunction cd{0}; cd {1}; end",
+            alias,
+            path.display()
+        )
+    }
+}
+
+struct BashFormatter;
+
+impl Formatter for BashFormatter {
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!(r#"alias cd{}="cd {}""#, alias, path.display())
+    }
+
+    fn render(&self, bookmarks: &[ResolvedBookmark<'_>]) -> String {
+        let cdpath = bookmarks
+            .iter()
+            .map(|b| b.path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(":");
+        let mut out = format!("export CDPATH=\".:{cdpath}\"\n");
+        for bookmark in bookmarks {
+            for alias in &bookmark.aliases {
+                out.push_str(&self.line(alias, &bookmark.path));
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+struct RangerFormatter;
+
+impl Formatter for RangerFormatter {
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!("{}:{}", alias, path.display())
+    }
+}
+
+struct NnnFormatter;
+
+impl Formatter for NnnFormatter {
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!("{}:{}", alias, path.display())
+    }
+
+    fn render(&self, bookmarks: &[ResolvedBookmark<'_>]) -> String {
+        let joined = bookmarks
+            .iter()
+            .flat_map(|b| b.aliases.iter().map(|alias| self.line(alias, &b.path)))
+            .collect::<Vec<_>>()
+            .join(";");
+        format!("export NNN_BMS=\"{joined}\"\n")
+    }
+}
+
+struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn header(&self) -> Option<String> {
+        Some("[".to_string())
+    }
+
+    fn line(&self, alias: &str, path: &Path) -> String {
+        format!(
+            r#"  {{"alias":"{}","path":"{}"}}"#,
+            json_escape(alias),
+            json_escape(&path.display().to_string())
+        )
+    }
+
+    fn separator(&self) -> &str {
+        ",\n"
+    }
+
+    fn footer(&self) -> Option<String> {
+        Some("]".to_string())
+    }
+}
+
+fn json_escape(raw: &str) -> String {
+    raw.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn bookmark<'a>(aliases: Vec<&'a str>, path: &str) -> ResolvedBookmark<'a> {
+        ResolvedBookmark {
+            aliases,
+            path: PathBuf::from(path),
+        }
+    }
+
+    #[test]
+    fn parses_known_backends() {
+        assert_eq!("lf".parse(), Ok(Backend::Lf));
+        assert_eq!("json".parse(), Ok(Backend::Json));
+    }
+
+    #[test]
+    fn rejects_unknown_backend() {
+        assert!("powershell".parse::<Backend>().is_err());
+    }
+
+    #[test]
+    fn lf_emits_one_line_per_alias() {
+        let bookmarks = vec![bookmark(vec!["h", "home"], "/home/user")];
+
+        let rendered = LfFormatter.render(&bookmarks);
+
+        assert_eq!(
+            rendered,
+            "map gh cd /home/user\nmap ghome cd /home/user\n"
+        );
+    }
+
+    #[test]
+    fn zsh_formats_named_dir() {
+        let bookmarks = vec![bookmark(vec!["home"], "/home/user")];
+
+        assert_eq!(
+            ZshFormatter.render(&bookmarks),
+            "hash -d home=/home/user\n"
+        );
+    }
+
+    #[test]
+    fn cd_alias_quotes_the_path() {
+        let bookmarks = vec![bookmark(vec!["home"], "/home/user")];
+
+        assert_eq!(
+            CdAliasFormatter.render(&bookmarks),
+            "alias cdhome=\"/home/user\"\n"
+        );
+    }
+
+    #[test]
+    fn fish_emits_abbr_and_function() {
+        let bookmarks = vec![bookmark(vec!["home"], "/home/user")];
+
+        assert_eq!(
+            FishFormatter.render(&bookmarks),
+            "abbr -a home 'cd /home/user'\nfunction cdhome; cd /home/user; end\n"
+        );
+    }
+
+    #[test]
+    fn ranger_formats_key_colon_path() {
+        let bookmarks = vec![bookmark(vec!["h"], "/home/user")];
+
+        assert_eq!(RangerFormatter.render(&bookmarks), "h:/home/user\n");
+    }
+
+    #[test]
+    fn bash_render_builds_cdpath_from_every_bookmark_and_aliases_every_line() {
+        let bookmarks = vec![
+            bookmark(vec!["home"], "/home/user"),
+            bookmark(vec!["d", "dl"], "/home/user/downloads"),
+        ];
+
+        let rendered = BashFormatter.render(&bookmarks);
+
+        assert_eq!(
+            rendered,
+            "export CDPATH=\".:/home/user:/home/user/downloads\"\n\
+             alias cdhome=\"cd /home/user\"\n\
+             alias cdd=\"cd /home/user/downloads\"\n\
+             alias cddl=\"cd /home/user/downloads\"\n"
+        );
+    }
+
+    #[test]
+    fn nnn_render_joins_every_alias_into_one_env_line() {
+        let bookmarks = vec![
+            bookmark(vec!["h"], "/home/user"),
+            bookmark(vec!["d", "dl"], "/home/user/downloads"),
+        ];
+
+        let rendered = NnnFormatter.render(&bookmarks);
+
+        assert_eq!(
+            rendered,
+            "export NNN_BMS=\"h:/home/user;d:/home/user/downloads;dl:/home/user/downloads\"\n"
+        );
+    }
+
+    #[test]
+    fn json_render_emits_an_array_with_one_object_per_alias() {
+        let bookmarks = vec![bookmark(vec!["h", "home"], "/home/user")];
+
+        let rendered = JsonFormatter.render(&bookmarks);
+
+        assert_eq!(
+            rendered,
+            "[\n  {\"alias\":\"h\",\"path\":\"/home/user\"},\n  \
+             {\"alias\":\"home\",\"path\":\"/home/user\"}\n]\n"
+        );
+    }
+
+    #[test]
+    fn json_render_of_an_empty_list_has_no_blank_line() {
+        let rendered = JsonFormatter.render(&[]);
+
+        assert_eq!(rendered, "[\n]\n");
+    }
+
+    #[test]
+    fn json_escapes_quotes_and_backslashes() {
+        assert_eq!(json_escape(r#"has "quotes""#), r#"has \"quotes\""#);
+        assert_eq!(json_escape(r"has\backslash"), r"has\\backslash");
+    }
+}
@@ -1,41 +1,49 @@
+mod backend;
+mod resolve;
+
 use anyhow::bail;
 use anyhow::Result;
+use std::borrow::Cow;
 use std::fs;
+use std::path::PathBuf;
 
-use clap::{Args, Parser};
+use clap::Parser;
 use nom::branch::alt;
 use nom::bytes::complete::{take_till1, take_while1};
 use nom::character::complete::space1;
 use nom::sequence::Tuple;
 use nom::IResult;
 
+use backend::Backend;
+use resolve::UnresolvedBookmark;
+
 #[derive(Parser)]
 struct Config {
     #[arg(short = 'i', long = "input")]
     bookmarks_file: String,
-    #[command(flatten)]
-    outputs: Outputs,
+    /// Treat a missing or non-directory bookmark target as a hard error
+    /// instead of a warning.
+    #[arg(long = "strict")]
+    strict: bool,
+    /// Output target to write, as `<backend>=<file>` (e.g.
+    /// `--emit lf=~/.config/lf/lfrc.d/bookmarks`). Repeatable; may be given
+    /// several times for the same backend to write multiple copies.
+    #[arg(long = "emit", required = true, value_parser = parse_emit)]
+    emits: Vec<(Backend, PathBuf)>,
 }
 
-#[derive(Args, Clone)]
-#[group(required = true, multiple = true)]
-struct Outputs {
-    #[arg(short = 'l', long = "lf")]
-    lf_file: Option<String>,
-    #[arg(short = 'z', long = "zsh")]
-    zsh_named_dirs_file: Option<String>,
-    #[arg(short = 'c', long = "cd-alias")]
-    cd_aliases_file: Option<String>,
+fn parse_emit(raw: &str) -> Result<(Backend, PathBuf), String> {
+    let (backend, path) = raw
+        .split_once('=')
+        .ok_or_else(|| format!("expected `<backend>=<file>`, got `{raw}`"))?;
+    Ok((backend.parse()?, PathBuf::from(path)))
 }
 
-macro_rules! write_formatted {
-    ($bookmarks:ident, $output_path:ident, $fmt:literal) => {{
-        let mut result = String::new();
-        for bookmark in &$bookmarks {
-            result.push_str(&format!(concat!($fmt, "\n"), bookmark.alias, bookmark.path));
-        }
-        fs::write($output_path, result)
-    }};
+/// A parsed bookmark whose target has gone through [`resolve`].
+#[derive(Debug)]
+pub struct ResolvedBookmark<'a> {
+    pub(crate) aliases: Vec<&'a str>,
+    pub(crate) path: PathBuf,
 }
 
 fn main() -> Result<()> {
@@ -57,46 +65,180 @@ fn main() -> Result<()> {
         Err(err) => bail!(err.to_string()),
     };
 
-    if let Some(output_path) = args.outputs.lf_file {
-        write_formatted!(bookmarks, output_path, "map g{} cd {}")?;
+    let bookmarks = resolve_bookmarks(bookmarks, args.strict)?;
+
+    for (backend, output_path) in args.emits {
+        let rendered = backend.formatter().render(&bookmarks);
+        fs::write(output_path, rendered)?;
     }
-    if let Some(output_path) = args.outputs.zsh_named_dirs_file {
-        write_formatted!(bookmarks, output_path, "hash -d {}={}")?;
+
+    Ok(())
+}
+
+/// Expands and canonicalizes every bookmark's path, reporting problems along
+/// the way.
+///
+/// In strict mode a single bad alias turns this into a hard error that lists
+/// every offending alias; otherwise each problem is printed to stderr as a
+/// warning and the path is kept so the entry is still written: canonicalized
+/// when the target exists but is not a directory, otherwise only expanded.
+fn resolve_bookmarks(
+    bookmarks: Vec<Bookmark<'_>>,
+    strict: bool,
+) -> Result<Vec<ResolvedBookmark<'_>>> {
+    let mut resolved = Vec::with_capacity(bookmarks.len());
+    let mut bad = Vec::new();
+
+    for bookmark in bookmarks {
+        let expanded = resolve::expand(&bookmark.path);
+        let alias = bookmark.aliases.join(",");
+        let path = match resolve::resolve(&bookmark.path) {
+            Ok(path) if path.is_dir() => path,
+            Ok(path) => {
+                bad.push(UnresolvedBookmark {
+                    alias,
+                    raw_path: bookmark.path.to_string(),
+                    reason: "not a directory".to_string(),
+                });
+                path
+            }
+            Err(err) => {
+                bad.push(UnresolvedBookmark {
+                    alias,
+                    raw_path: bookmark.path.to_string(),
+                    reason: err.to_string(),
+                });
+                PathBuf::from(expanded)
+            }
+        };
+        resolved.push(ResolvedBookmark {
+            aliases: bookmark.aliases,
+            path,
+        });
     }
-    if let Some(output_path) = args.outputs.cd_aliases_file {
-        write_formatted!(bookmarks, output_path, r#"alias cd{}="{}""#)?;
+
+    if strict && !bad.is_empty() {
+        let list = bad
+            .iter()
+            .map(|b| format!("  {} -> {} ({})", b.alias, b.raw_path, b.reason))
+            .collect::<Vec<_>>()
+            .join("\n");
+        bail!("bookmark targets failed to resolve:\n{list}");
     }
 
-    Ok(())
+    for b in &bad {
+        eprintln!(
+            "warning: bookmark '{}' target '{}' {}",
+            b.alias, b.raw_path, b.reason
+        );
+    }
+
+    Ok(resolved)
 }
 
 #[derive(Debug, PartialEq)]
 struct Bookmark<'a> {
-    alias: &'a str,
-    path: &'a str,
+    aliases: Vec<&'a str>,
+    path: Cow<'a, str>,
 }
 
 fn till_space(input: &str) -> IResult<&str, &str, nom::error::VerboseError<&str>> {
-    take_while1(|c| c != ' ')(input)
-}
-
-fn till_whitespace_or_hash(input: &str) -> IResult<&str, &str, nom::error::VerboseError<&str>> {
-    take_till1(|c: char| c.is_whitespace() || c == '#')(input)
+    take_while1(|c: char| !c.is_whitespace())(input)
 }
 
 fn quote(input: &str) -> IResult<&str, &str, nom::error::VerboseError<&str>> {
     take_while1(|c| c == '"')(input)
 }
 
+/// Parses an unquoted path, stopping at the first Unicode whitespace
+/// character (space, tab, NBSP, ...) or unescaped `#`.
+///
+/// `\ ` and `\#` are recognized as escapes for a literal space/hash; any
+/// other backslash is kept as-is. Borrows the input when no escape is
+/// present, and only allocates an owned `String` once one is found.
+fn simple_path(input: &str) -> IResult<&str, Cow<'_, str>, nom::error::VerboseError<&str>> {
+    use nom::error::ParseError;
+
+    let mut pos = 0;
+    let mut owned: Option<String> = None;
+
+    while let Some(c) = input[pos..].chars().next() {
+        if c == '\\' {
+            let after_backslash = pos + c.len_utf8();
+            if let Some(escaped @ (' ' | '#')) = input[after_backslash..].chars().next() {
+                let buf = owned.get_or_insert_with(|| input[..pos].to_string());
+                buf.push(escaped);
+                pos = after_backslash + escaped.len_utf8();
+                continue;
+            }
+        }
+
+        if c.is_whitespace() || c == '#' {
+            break;
+        }
+
+        if let Some(buf) = owned.as_mut() {
+            buf.push(c);
+        }
+        pos += c.len_utf8();
+    }
+
+    if pos == 0 {
+        return Err(nom::Err::Error(nom::error::VerboseError::from_error_kind(
+            input,
+            nom::error::ErrorKind::TakeWhile1,
+        )));
+    }
+
+    let path = match owned {
+        Some(s) => Cow::Owned(s),
+        None => Cow::Borrowed(&input[..pos]),
+    };
+    Ok((&input[pos..], path))
+}
+
+/// Parses the first field of a bookmark line as a comma-separated list of
+/// aliases, e.g. `home,h,~`. Fails if any alias is empty or repeated within
+/// the line.
+fn alias_list(input: &str) -> IResult<&str, Vec<&str>, nom::error::VerboseError<&str>> {
+    use nom::error::{ErrorKind, ParseError, VerboseError};
+
+    let (rest, raw) = till_space(input)?;
+    let aliases: Vec<&str> = raw.split(',').collect();
+
+    if aliases.iter().any(|alias| alias.is_empty()) {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Verify,
+        )));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    if !aliases.iter().all(|alias| seen.insert(*alias)) {
+        return Err(nom::Err::Failure(VerboseError::from_error_kind(
+            input,
+            ErrorKind::Verify,
+        )));
+    }
+
+    Ok((rest, aliases))
+}
+
 fn with_simple_path(input: &str) -> IResult<&str, Bookmark<'_>, nom::error::VerboseError<&str>> {
-    let (rest, (alias, _, path)) = (till_space, space1, till_whitespace_or_hash).parse(input)?;
-    Ok((rest, Bookmark { alias, path }))
+    let (rest, (aliases, _, path)) = (alias_list, space1, simple_path).parse(input)?;
+    Ok((rest, Bookmark { aliases, path }))
 }
 
 fn with_quoted_path(input: &str) -> IResult<&str, Bookmark<'_>, nom::error::VerboseError<&str>> {
-    let (rest, (alias, _, _, path, _)) =
-        (till_space, space1, quote, take_till1(|c| c == '"'), quote).parse(input)?;
-    Ok((rest, Bookmark { alias, path }))
+    let (rest, (aliases, _, _, path, _)) =
+        (alias_list, space1, quote, take_till1(|c| c == '"'), quote).parse(input)?;
+    Ok((
+        rest,
+        Bookmark {
+            aliases,
+            path: Cow::Borrowed(path),
+        },
+    ))
 }
 
 fn bookmark(input: &str) -> IResult<&str, Bookmark<'_>, nom::error::VerboseError<&str>> {
@@ -106,7 +248,8 @@ fn bookmark(input: &str) -> IResult<&str, Bookmark<'_>, nom::error::VerboseError
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use crate::bookmark;
+    use crate::{bookmark, resolve_bookmarks, Bookmark};
+    use std::borrow::Cow;
 
     #[test]
     fn only_considers_till_second_space() {
@@ -114,7 +257,7 @@ mod tests {
 
         let result = bookmark(input).unwrap().1;
 
-        assert_eq!(result.alias, "a");
+        assert_eq!(result.aliases, vec!["a"]);
         assert_eq!(result.path, "b");
     }
 
@@ -124,7 +267,7 @@ mod tests {
 
         let result = bookmark(input).unwrap().1;
 
-        assert_eq!(result.alias, "a");
+        assert_eq!(result.aliases, vec!["a"]);
         assert_eq!(result.path, "b");
     }
 
@@ -134,7 +277,136 @@ mod tests {
 
         let result = bookmark(input).unwrap().1;
 
-        assert_eq!(result.alias, "a");
+        assert_eq!(result.aliases, vec!["a"]);
         assert_eq!(result.path, "test test #test");
     }
+
+    #[test]
+    fn parses_comma_separated_aliases() {
+        let input = "home,h,~ /home/user";
+
+        let result = bookmark(input).unwrap().1;
+
+        assert_eq!(result.aliases, vec!["home", "h", "~"]);
+        assert_eq!(result.path, "/home/user");
+    }
+
+    #[test]
+    fn rejects_empty_alias_in_list() {
+        let input = "home,,h /home/user";
+
+        assert!(bookmark(input).is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_alias_in_list() {
+        let input = "home,h,home /home/user";
+
+        assert!(bookmark(input).is_err());
+    }
+
+    #[test]
+    fn unquoted_path_stops_at_tab() {
+        let input = "a b\tc";
+
+        let result = bookmark(input).unwrap().1;
+
+        assert_eq!(result.path, "b");
+    }
+
+    #[test]
+    fn unquoted_path_stops_at_nbsp() {
+        let input = "a b\u{a0}c";
+
+        let result = bookmark(input).unwrap().1;
+
+        assert_eq!(result.path, "b");
+    }
+
+    #[test]
+    fn unquoted_path_allows_escaped_space() {
+        let input = r"a /srv/My\ Music c";
+
+        let result = bookmark(input).unwrap().1;
+
+        assert_eq!(result.path, "/srv/My Music");
+        assert!(matches!(result.path, std::borrow::Cow::Owned(_)));
+    }
+
+    #[test]
+    fn unquoted_path_allows_escaped_hash() {
+        let input = r"a /srv/My\#Music c";
+
+        let result = bookmark(input).unwrap().1;
+
+        assert_eq!(result.path, "/srv/My#Music");
+    }
+
+    #[test]
+    fn unescaped_path_stays_borrowed() {
+        let input = "a /srv/music c";
+
+        let result = bookmark(input).unwrap().1;
+
+        assert!(matches!(result.path, std::borrow::Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn resolve_bookmarks_accepts_an_existing_directory() {
+        let bookmarks = vec![Bookmark {
+            aliases: vec!["tmp"],
+            path: Cow::Owned(std::env::temp_dir().to_string_lossy().into_owned()),
+        }];
+
+        let resolved = resolve_bookmarks(bookmarks, true).unwrap();
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].aliases, vec!["tmp"]);
+    }
+
+    #[test]
+    fn resolve_bookmarks_lenient_keeps_unresolvable_entries() {
+        let bookmarks = vec![Bookmark {
+            aliases: vec!["missing"],
+            path: Cow::Borrowed("/definitely/does/not/exist/crossmarks-test"),
+        }];
+
+        let resolved = resolve_bookmarks(bookmarks, false).unwrap();
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].aliases, vec!["missing"]);
+    }
+
+    #[test]
+    fn resolve_bookmarks_strict_errors_on_missing_target() {
+        let bookmarks = vec![Bookmark {
+            aliases: vec!["missing"],
+            path: Cow::Borrowed("/definitely/does/not/exist/crossmarks-test"),
+        }];
+
+        let err = resolve_bookmarks(bookmarks, true).unwrap_err();
+
+        assert!(err.to_string().contains("missing"));
+    }
+
+    #[test]
+    fn resolve_bookmarks_strict_errors_on_non_directory_target() {
+        let file = std::env::temp_dir().join(format!(
+            "crossmarks-test-file-{}-{}",
+            std::process::id(),
+            line!()
+        ));
+        std::fs::write(&file, "not a directory").unwrap();
+
+        let bookmarks = vec![Bookmark {
+            aliases: vec!["file"],
+            path: Cow::Owned(file.to_string_lossy().into_owned()),
+        }];
+
+        let err = resolve_bookmarks(bookmarks, true).unwrap_err();
+
+        std::fs::remove_file(&file).unwrap();
+
+        assert!(err.to_string().contains("not a directory"));
+    }
 }
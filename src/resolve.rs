@@ -0,0 +1,150 @@
+use std::env;
+use std::path::PathBuf;
+
+/// A bookmark whose target directory failed resolution.
+///
+/// Carries enough context to report a useful error/warning without
+/// re-deriving the reason from the path itself.
+pub struct UnresolvedBookmark {
+    pub alias: String,
+    pub raw_path: String,
+    pub reason: String,
+}
+
+/// Expands `~`, `~user`, and `$VAR`/`${VAR}` references in `raw` against the
+/// current environment. Does not touch the filesystem.
+///
+/// Only `~` and `~$USER` are resolvable without a passwd-database lookup;
+/// any other `~user` is left untouched.
+pub fn expand(raw: &str) -> String {
+    expand_vars(&expand_tilde(raw))
+}
+
+/// Expands and canonicalizes `raw`, returning the resulting absolute path.
+///
+/// On success the returned path is guaranteed to exist. Callers that want to
+/// additionally require a directory should check `PathBuf::is_dir` on the
+/// result (canonicalization alone accepts any existing filesystem entry).
+pub fn resolve(raw: &str) -> std::io::Result<PathBuf> {
+    std::fs::canonicalize(expand(raw))
+}
+
+fn expand_tilde(raw: &str) -> String {
+    let Some(rest) = raw.strip_prefix('~') else {
+        return raw.to_string();
+    };
+    let split_at = rest.find(['/', '\\']).unwrap_or(rest.len());
+    let (user, tail) = rest.split_at(split_at);
+
+    let current_user_matches = user.is_empty() || env::var("USER").is_ok_and(|u| u == user);
+    let home = if current_user_matches {
+        env::var("HOME").ok()
+    } else {
+        None
+    };
+
+    match home {
+        Some(home) => format!("{home}{tail}"),
+        None => raw.to_string(),
+    }
+}
+
+fn expand_vars(raw: &str) -> String {
+    let mut result = String::with_capacity(raw.len());
+    let mut pos = 0;
+
+    while pos < raw.len() {
+        let c = raw[pos..].chars().next().expect("pos is a char boundary");
+        if c != '$' {
+            result.push(c);
+            pos += c.len_utf8();
+            continue;
+        }
+
+        let rest = &raw[pos + 1..];
+        let (name, consumed) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], end)
+        };
+
+        if name.is_empty() {
+            result.push('$');
+            pos += 1;
+            continue;
+        }
+
+        if let Ok(value) = env::var(name) {
+            result.push_str(&value);
+        }
+        // `consumed` is a byte count into `rest`; slice by bytes rather than
+        // stepping a char iterator, since the name may contain multi-byte
+        // Unicode characters.
+        pos += 1 + consumed;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // `std::env::set_var`/`remove_var` mutate process-global state, but the
+    // test harness runs tests in separate threads. Hold this for the
+    // duration of any test that touches an env var so two such tests can
+    // never interleave their reads/writes.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn expands_home_tilde() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("HOME", "/home/alice");
+        assert_eq!(expand_tilde("~/projects"), "/home/alice/projects");
+    }
+
+    #[test]
+    fn leaves_bare_tilde_unresolved_without_home() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("HOME");
+        assert_eq!(expand_tilde("~/projects"), "~/projects");
+        std::env::set_var("HOME", "/home/alice");
+    }
+
+    #[test]
+    fn expands_simple_var() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("CRATE_TEST_VAR", "value");
+        assert_eq!(expand_vars("$CRATE_TEST_VAR/sub"), "value/sub");
+    }
+
+    #[test]
+    fn expands_braced_var() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("CRATE_TEST_VAR", "value");
+        assert_eq!(expand_vars("${CRATE_TEST_VAR}sub"), "valuesub");
+    }
+
+    #[test]
+    fn does_not_swallow_bytes_after_a_multibyte_var_name() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("café", "VALUE");
+        assert_eq!(expand_vars("$café/sub"), "VALUE/sub");
+        assert_eq!(expand_vars("${café}/sub"), "VALUE/sub");
+        std::env::remove_var("café");
+    }
+
+    #[test]
+    fn leaves_unknown_var_empty() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("CRATE_TEST_VAR_UNSET");
+        assert_eq!(expand_vars("$CRATE_TEST_VAR_UNSET/sub"), "/sub");
+    }
+}